@@ -0,0 +1,146 @@
+use gfx::{Frame, Graphics, Plane};
+
+use gfx::traits::*;
+
+use gfx::tex::{TextureInfo, TextureKind, Format, ImageInfo, Size};
+
+///
+/// Render whatever `draw` issues into an offscreen RGBA8 target of `size`
+/// (pixels) and read the pixels back to CPU memory, row-major and flipped
+/// vertically to match image conventions (origin top-left). Pass `crop` to
+/// only return a sub-rectangle (min, max) of the rendered target.
+///
+/// NOTE: this crate's `DebugRenderer` -- the facade that owns the line,
+/// text, and quad renderers and is the natural home for a `capture` method
+/// -- is not part of this source tree, so this ships as a standalone helper instead of
+/// `DebugRenderer::capture`. Wire it in as that method once `DebugRenderer`
+/// is available, passing a `draw` closure that calls through to each
+/// sub-renderer's own `render`.
+///
+pub fn capture<D, F, DrawFn>(
+    factory: &mut F,
+    graphics: &mut Graphics<D>,
+    size: [u32; 2],
+    crop: Option<([u32; 2], [u32; 2])>,
+    draw: DrawFn,
+) -> Vec<u8>
+where
+    D: Device,
+    F: Factory<D::Resources>,
+    DrawFn: FnOnce(&mut Graphics<D>, &Frame<D::Resources>),
+{
+    let [width, height] = size;
+
+    let target_texture = factory.create_texture(TextureInfo {
+        width: width as Size,
+        height: height as Size,
+        depth: 1,
+        levels: 1,
+        kind: TextureKind::Texture2D,
+        format: Format::Rgba8,
+    }).unwrap();
+
+    // Point the frame's color plane at our offscreen texture, so `draw`
+    // renders into it instead of the default (window) framebuffer.
+    let mut frame = Frame::new(width as u16, height as u16);
+    frame.colors.push(Plane::Texture(target_texture.clone(), 0, None));
+
+    draw(graphics, &frame);
+
+    let mut pixels = factory.read_texture(&target_texture, &ImageInfo {
+        xoffset: 0,
+        yoffset: 0,
+        zoffset: 0,
+        width: width as Size,
+        height: height as Size,
+        depth: 1,
+        format: Format::Rgba8,
+        mipmap: 0,
+    });
+
+    flip_vertically(&mut pixels, width as usize, height as usize);
+
+    match crop {
+        Some((min, max)) => crop_pixels(&pixels, width as usize, min, max),
+        None => pixels,
+    }
+}
+
+const BYTES_PER_PIXEL: usize = 4;
+
+fn flip_vertically(pixels: &mut [u8], width: usize, height: usize) {
+    let row_bytes = width * BYTES_PER_PIXEL;
+
+    for row in 0 .. height / 2 {
+        let top = row * row_bytes;
+        let bottom = (height - 1 - row) * row_bytes;
+
+        for i in 0 .. row_bytes {
+            pixels.swap(top + i, bottom + i);
+        }
+    }
+}
+
+fn crop_pixels(pixels: &[u8], width: usize, min: [u32; 2], max: [u32; 2]) -> Vec<u8> {
+    let crop_width = (max[0] - min[0]) as usize;
+    let crop_height = (max[1] - min[1]) as usize;
+    let mut cropped = Vec::with_capacity(crop_width * crop_height * BYTES_PER_PIXEL);
+
+    for row in 0 .. crop_height {
+        let src_row = min[1] as usize + row;
+        let src_start = (src_row * width + min[0] as usize) * BYTES_PER_PIXEL;
+        let src_end = src_start + crop_width * BYTES_PER_PIXEL;
+        cropped.extend_from_slice(&pixels[src_start .. src_end]);
+    }
+
+    cropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flip_vertically, crop_pixels};
+
+    #[test]
+    fn flip_vertically_swaps_rows() {
+        // 2x2 RGBA8: row 0 red, row 1 blue
+        let mut pixels = vec![
+            255, 0, 0, 255,  255, 0, 0, 255,
+            0, 0, 255, 255,  0, 0, 255, 255,
+        ];
+
+        flip_vertically(&mut pixels, 2, 2);
+
+        assert_eq!(&pixels[0 .. 8], &[0, 0, 255, 255, 0, 0, 255, 255][..]);
+        assert_eq!(&pixels[8 .. 16], &[255, 0, 0, 255, 255, 0, 0, 255][..]);
+    }
+
+    #[test]
+    fn flip_vertically_is_a_noop_for_a_single_row() {
+        let mut pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let expected = pixels.clone();
+
+        flip_vertically(&mut pixels, 2, 1);
+
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn crop_pixels_extracts_a_sub_rectangle() {
+        // 3x2 RGBA8, pixel value == (row, col) packed into the red channel
+        let mut pixels = Vec::new();
+        for row in 0 .. 2u8 {
+            for col in 0 .. 3u8 {
+                pixels.extend_from_slice(&[row * 10 + col, 0, 0, 255]);
+            }
+        }
+
+        let cropped = crop_pixels(&pixels, 3, [1, 0], [3, 2]);
+
+        assert_eq!(cropped, vec![
+            1, 0, 0, 255,    // (row 0, col 1) -> value 1
+            2, 0, 0, 255,    // (row 0, col 2) -> value 2
+            11, 0, 0, 255,   // (row 1, col 1) -> value 11
+            12, 0, 0, 255,   // (row 1, col 2) -> value 12
+        ]);
+    }
+}
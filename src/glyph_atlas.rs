@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+///
+/// Location and metrics of a single rasterized glyph inside a `GlyphAtlas`.
+/// Mirrors the fields `draw_text` reads off `bitmap_font.characters` so the
+/// existing quad-emitting loop can consume either source unchanged.
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GlyphInfo {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub xoffset: i32,
+    pub yoffset: i32,
+    pub xadvance: i32,
+}
+
+/// A horizontal strip of the atlas that packs glyphs left-to-right.
+struct Shelf {
+    y: u32,
+    height: u32,
+    cursor_x: u32,
+}
+
+///
+/// Skyline/shelf bin-packer backing runtime glyph rasterization. Glyphs are
+/// placed into rows ("shelves"): a new glyph either joins the lowest shelf
+/// tall enough to hold it, or starts a fresh shelf below the previous ones.
+/// When no shelf has room and there is no space left for a new one, the
+/// atlas doubles its height and every glyph already packed keeps its slot.
+///
+pub struct GlyphAtlas {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+    glyphs: HashMap<char, GlyphInfo>,
+    pixels: Vec<u8>,
+}
+
+impl GlyphAtlas {
+
+    pub fn new(width: u32, height: u32) -> GlyphAtlas {
+        GlyphAtlas {
+            width: width,
+            height: height,
+            shelves: Vec::new(),
+            glyphs: HashMap::new(),
+            pixels: vec![0u8; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 { self.width }
+    pub fn height(&self) -> u32 { self.height }
+
+    pub fn get(&self, character: char) -> Option<&GlyphInfo> {
+        self.glyphs.get(&character)
+    }
+
+    ///
+    /// Copy a single-channel (alpha) glyph bitmap into the atlas, growing it
+    /// if necessary, and record the resulting `GlyphInfo`. Returns `None`
+    /// without modifying the atlas if `width` exceeds the atlas's fixed
+    /// width -- growing (which only ever doubles height) can never make the
+    /// glyph fit, so the caller should fall back to rendering it as missing
+    /// rather than loop forever.
+    ///
+    pub fn insert(
+        &mut self,
+        character: char,
+        bitmap: &[u8],
+        width: u32,
+        height: u32,
+        xoffset: i32,
+        yoffset: i32,
+        xadvance: i32,
+    ) -> Option<&GlyphInfo> {
+
+        let (shelf_index, x) = match self.find_shelf(width, height) {
+            Some(found) => found,
+            None => return None,
+        };
+
+        let y = self.shelves[shelf_index].y;
+        self.blit(bitmap, width, height, x, y);
+        self.shelves[shelf_index].cursor_x = x + width;
+
+        self.glyphs.insert(character, GlyphInfo {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+            xoffset: xoffset,
+            yoffset: yoffset,
+            xadvance: xadvance,
+        });
+
+        self.glyphs.get(&character)
+    }
+
+    /// Find the lowest shelf with enough height and remaining width for a
+    /// glyph of this size, opening new shelves (and growing the atlas once
+    /// there's no room left for one) until it fits. Returns `None` if
+    /// `width` alone already exceeds the atlas's fixed width, since no
+    /// amount of (height-only) growth would ever make it fit.
+    fn find_shelf(&mut self, width: u32, height: u32) -> Option<(usize, u32)> {
+        if width > self.width {
+            return None;
+        }
+
+        loop {
+            if let Some(found) = self.shelves.iter().enumerate()
+                .filter(|&(_, shelf)| shelf.height >= height)
+                .map(|(i, shelf)| (i, shelf.cursor_x))
+                .find(|&(_, cursor_x)| cursor_x + width <= self.width) {
+                return Some(found);
+            }
+
+            if self.open_shelf(height).is_none() {
+                // a new shelf was opened; loop back and place the glyph in it
+                continue;
+            }
+
+            // atlas is full: no room for a new shelf, so grow and retry
+            self.grow();
+        }
+    }
+
+    /// Append a new shelf at the bottom of the packed region if there is
+    /// still vertical room for it. Returns `None` when a shelf was opened
+    /// (caller should retry `find_shelf`), or `Some(())` when the atlas is
+    /// already full and needs to grow instead.
+    fn open_shelf(&mut self, height: u32) -> Option<()> {
+        let y = self.shelves.iter().map(|s| s.y + s.height).max().unwrap_or(0);
+
+        if y + height > self.height {
+            return Some(());
+        }
+
+        self.shelves.push(Shelf { y: y, height: height, cursor_x: 0 });
+        None
+    }
+
+    /// Double the atlas height, preserving every previously packed glyph's
+    /// pixels and `GlyphInfo`.
+    fn grow(&mut self) {
+        let new_height = self.height * 2;
+        let mut new_pixels = vec![0u8; (self.width * new_height) as usize];
+        new_pixels[.. self.pixels.len()].copy_from_slice(&self.pixels);
+        self.pixels = new_pixels;
+        self.height = new_height;
+    }
+
+    fn blit(&mut self, bitmap: &[u8], width: u32, height: u32, x: u32, y: u32) {
+        for row in 0 .. height {
+            let src_start = (row * width) as usize;
+            let dst_start = ((y + row) * self.width + x) as usize;
+            let len = width as usize;
+            self.pixels[dst_start .. dst_start + len]
+                .copy_from_slice(&bitmap[src_start .. src_start + len]);
+        }
+    }
+
+    /// Row-major single-channel pixels of the atlas, `width() * height()`
+    /// bytes, suitable for uploading as a texture.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlyphAtlas;
+
+    #[test]
+    fn packs_glyphs_onto_the_same_shelf() {
+        let mut atlas = GlyphAtlas::new(16, 16);
+
+        let a = *atlas.insert('a', &[0u8; 16], 4, 4, 0, 0, 4).unwrap();
+        let b = *atlas.insert('b', &[0u8; 16], 4, 4, 0, 0, 4).unwrap();
+
+        assert_eq!(a.y, b.y);
+        assert_eq!(b.x, a.x + 4);
+        assert_eq!(atlas.height(), 16);
+    }
+
+    #[test]
+    fn opens_a_new_shelf_without_growing() {
+        let mut atlas = GlyphAtlas::new(16, 16);
+
+        let first = *atlas.insert('a', &[0u8; 64], 16, 4, 0, 0, 16).unwrap();
+        let second = *atlas.insert('b', &[0u8; 64], 16, 4, 0, 0, 16).unwrap();
+
+        assert_eq!(first.y, 0);
+        assert_eq!(second.y, 4);
+        assert_eq!(atlas.height(), 16);
+    }
+
+    #[test]
+    fn grows_instead_of_panicking_once_every_shelf_is_full() {
+        let mut atlas = GlyphAtlas::new(8, 8);
+
+        // Each glyph fills an entire 8-wide row, so every insert opens a new
+        // shelf. The third one no longer fits below the first two (8 px
+        // tall atlas, 4 px rows) and must force a grow() rather than panic.
+        for i in 0 .. 4 {
+            let character = (b'a' + i as u8) as char;
+            let info = *atlas.insert(character, &[0u8; 32], 8, 4, 0, 0, 8).unwrap();
+            assert_eq!(info.y, i * 4);
+        }
+
+        assert_eq!(atlas.height(), 16);
+        assert!(atlas.get('a').is_some());
+        assert!(atlas.get('d').is_some());
+    }
+
+    #[test]
+    fn refuses_a_glyph_wider_than_the_atlas_instead_of_growing_forever() {
+        let mut atlas = GlyphAtlas::new(8, 8);
+
+        // No amount of (height-only) growth ever makes a 16px-wide glyph
+        // fit into an 8px-wide atlas -- insert must report failure rather
+        // than looping forever doubling the height.
+        let result = atlas.insert('a', &[0u8; 16 * 4], 16, 4, 0, 0, 16);
+
+        assert!(result.is_none());
+        assert_eq!(atlas.height(), 8);
+        assert!(atlas.get('a').is_none());
+    }
+}
@@ -0,0 +1,409 @@
+use std::mem;
+
+use gfx::{
+    as_byte_slice,
+    BlendPreset,
+    BufferHandle,
+    IndexBufferHandle,
+    BufferUsage,
+    DrawState,
+    Frame,
+    Graphics,
+    Mesh,
+    PrimitiveType,
+    ProgramError,
+    ProgramHandle,
+    SamplerHandle,
+    ShaderSource,
+    Slice,
+    SliceKind,
+    VertexCount,
+    TextureHandle,
+};
+
+use gfx::device::Capabilities;
+
+use gfx::traits::*;
+
+use gfx::tex::{SamplerInfo, FilterMethod, WrapMode, TextureInfo, TextureKind, Format, ImageInfo, Size};
+
+use gfx::batch::bind;
+
+use text_renderer::{Vertex, TextShaderParams, VERTEX_SRC, FRAGMENT_SRC};
+use utils::{grow_buffer, MAT4_ID};
+
+/// Fragment shader variant for `draw_textured_quad_on_screen`: samples the
+/// bound texture's full RGBA. `FRAGMENT_SRC` (reused for the solid-fill path)
+/// treats the texture as TextRenderer's single-channel glyph-atlas mask and
+/// discards its RGB, so it can't show a textured quad's actual image colors.
+static TEXTURED_FRAGMENT_SRC: [&'static [u8]; 4] = [
+b"
+    #version 120
+
+    uniform sampler2D u_tex_font;
+
+    varying vec4 v_color;
+    varying vec2 v_TexCoord;
+
+    void main() {
+        gl_FragColor = texture2D(u_tex_font, v_TexCoord) * v_color;
+    }
+",
+b"
+    #version 150 core
+
+    uniform sampler2D u_tex_font;
+
+    in vec4 v_color;
+    in vec2 v_TexCoord;
+    out vec4 out_color;
+
+    void main() {
+        out_color = texture(u_tex_font, v_TexCoord) * v_color;
+    }
+",
+b"
+    #version 100
+
+    precision mediump float;
+
+    uniform sampler2D u_tex_font;
+
+    varying vec4 v_color;
+    varying vec2 v_TexCoord;
+
+    void main() {
+        gl_FragColor = texture2D(u_tex_font, v_TexCoord) * v_color;
+    }
+",
+b"
+    #version 300 es
+
+    precision mediump float;
+
+    uniform sampler2D u_tex_font;
+
+    in vec4 v_color;
+    in vec2 v_TexCoord;
+    out vec4 out_color;
+
+    void main() {
+        out_color = texture(u_tex_font, v_TexCoord) * v_color;
+    }
+"];
+
+/// One contiguous run of the index buffer drawn with a single bound texture
+/// and fragment shader (see `textured` on `QuadRenderer::render`).
+struct Batch<D: Device> {
+    texture: TextureHandle<D::Resources>,
+    textured: bool,
+    index_start: u32,
+    index_end: u32,
+}
+
+///
+/// Draws screen-space filled rectangles and textured quads -- panels,
+/// highlight boxes, and image thumbnails for visual debugging. Built on the
+/// same batched quad pipeline (vertex format, `u_screen_size` normalization)
+/// as `TextRenderer`. Solid fills reuse `TextRenderer`'s fragment shader with
+/// a 1x1 white texture, so `font_color.a * v_color.a` still produces a flat
+/// fill; textured quads use their own RGBA-sampling shader so the texture's
+/// actual colors show through instead of just its alpha.
+///
+pub struct QuadRenderer<D: Device> {
+    program: ProgramHandle<D::Resources>,
+    textured_program: ProgramHandle<D::Resources>,
+    state: DrawState,
+    white_texture: TextureHandle<D::Resources>,
+    sampler: SamplerHandle<D::Resources>,
+    vertex_data: Vec<Vertex>,
+    index_data: Vec<u32>,
+    batches: Vec<Batch<D>>,
+    vertex_buffer: BufferHandle<D::Resources, Vertex>,
+    index_buffer: IndexBufferHandle<D::Resources, u32>,
+    params: TextShaderParams<D::Resources>,
+}
+
+impl<D: Device> QuadRenderer<D> {
+
+    pub fn new<F: Factory<D::Resources>>(
+        device_capabilities: Capabilities,
+        factory: &mut F,
+        frame_size: [u32; 2],
+        initial_buffer_size: usize,
+    ) -> Result<QuadRenderer<D>, ProgramError> {
+
+        let shader_model = device_capabilities.shader_model;
+
+        let vertex = ShaderSource {
+            glsl_120: Some(VERTEX_SRC[0]),
+            glsl_150: Some(VERTEX_SRC[1]),
+            glsl_es_100: Some(VERTEX_SRC[2]),
+            glsl_es_300: Some(VERTEX_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let fragment = ShaderSource {
+            glsl_120: Some(FRAGMENT_SRC[0]),
+            glsl_150: Some(FRAGMENT_SRC[1]),
+            glsl_es_100: Some(FRAGMENT_SRC[2]),
+            glsl_es_300: Some(FRAGMENT_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let program = match factory.link_program(
+            vertex.choose(shader_model).unwrap(),
+            fragment.choose(shader_model).unwrap()
+        ) {
+            Ok(program_handle) => program_handle,
+            Err(e) => return Err(e),
+        };
+
+        let textured_vertex = ShaderSource {
+            glsl_120: Some(VERTEX_SRC[0]),
+            glsl_150: Some(VERTEX_SRC[1]),
+            glsl_es_100: Some(VERTEX_SRC[2]),
+            glsl_es_300: Some(VERTEX_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let textured_fragment = ShaderSource {
+            glsl_120: Some(TEXTURED_FRAGMENT_SRC[0]),
+            glsl_150: Some(TEXTURED_FRAGMENT_SRC[1]),
+            glsl_es_100: Some(TEXTURED_FRAGMENT_SRC[2]),
+            glsl_es_300: Some(TEXTURED_FRAGMENT_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let textured_program = match factory.link_program(
+            textured_vertex.choose(shader_model).unwrap(),
+            textured_fragment.choose(shader_model).unwrap()
+        ) {
+            Ok(program_handle) => program_handle,
+            Err(e) => return Err(e),
+        };
+
+        let vertex_buffer = factory.create_buffer::<Vertex>(initial_buffer_size, BufferUsage::Dynamic);
+        let index_buffer = IndexBufferHandle::from_raw(factory.create_buffer_raw(initial_buffer_size * mem::size_of::<u32>(), BufferUsage::Dynamic));
+
+        let sampler = factory.create_sampler(
+           SamplerInfo::new(
+               FilterMethod::Scale,
+               WrapMode::Clamp
+            )
+        );
+
+        let white_texture = create_white_texture(factory);
+
+        let state = DrawState::new().blend(BlendPreset::Alpha);
+
+        Ok(QuadRenderer {
+            vertex_data: Vec::new(),
+            index_data: Vec::new(),
+            batches: Vec::new(),
+            program: program,
+            textured_program: textured_program,
+            state: state,
+            white_texture: white_texture.clone(),
+            sampler: sampler.clone(),
+            vertex_buffer: vertex_buffer,
+            index_buffer: index_buffer,
+            params: TextShaderParams {
+                u_model_view_proj: MAT4_ID,
+                u_screen_size: [frame_size[0] as f32, frame_size[1] as f32],
+                u_tex_font: (white_texture, Some(sampler)),
+            },
+        })
+    }
+
+    ///
+    /// Respond to a change in window size
+    ///
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.params.u_screen_size = [width as f32, height as f32];
+    }
+
+    /// Draw a solid-filled screen-space rectangle from `min` to `max`
+    /// (in pixels, origin top-left, matching `draw_text_on_screen`).
+    pub fn draw_rect_on_screen(&mut self, min: [i32; 2], max: [i32; 2], color: [f32; 4]) {
+        let white_texture = self.white_texture.clone();
+        self.push_quad(min, max, [0.0, 0.0], [1.0, 1.0], color, white_texture, false);
+    }
+
+    /// Draw a screen-space quad sampling `texcoords` (min, max UV corners)
+    /// from `texture`'s actual RGBA, tinted by multiplying with white
+    /// (fully opaque). Useful for image thumbnails or any other texture the
+    /// debug overlay wants to show.
+    pub fn draw_textured_quad_on_screen(
+        &mut self,
+        rect: ([i32; 2], [i32; 2]),
+        texcoords: ([f32; 2], [f32; 2]),
+        texture: TextureHandle<D::Resources>,
+    ) {
+        let (min, max) = rect;
+        let (uv_min, uv_max) = texcoords;
+        self.push_quad(min, max, uv_min, uv_max, [1.0, 1.0, 1.0, 1.0], texture, true);
+    }
+
+    fn push_quad(
+        &mut self,
+        min: [i32; 2],
+        max: [i32; 2],
+        uv_min: [f32; 2],
+        uv_max: [f32; 2],
+        color: [f32; 4],
+        texture: TextureHandle<D::Resources>,
+        textured: bool,
+    ) {
+        let index = self.vertex_data.len();
+
+        // 0 - top left
+        self.vertex_data.push(Vertex {
+            position: [min[0] as f32, min[1] as f32],
+            texcoords: [uv_min[0], uv_min[1]],
+            world_position: [0.0, 0.0, 0.0],
+            screen_relative: 1.0,
+            color: color,
+        });
+
+        // 1 - bottom left
+        self.vertex_data.push(Vertex {
+            position: [min[0] as f32, max[1] as f32],
+            texcoords: [uv_min[0], uv_max[1]],
+            world_position: [0.0, 0.0, 0.0],
+            screen_relative: 1.0,
+            color: color,
+        });
+
+        // 2 - bottom right
+        self.vertex_data.push(Vertex {
+            position: [max[0] as f32, max[1] as f32],
+            texcoords: [uv_max[0], uv_max[1]],
+            world_position: [0.0, 0.0, 0.0],
+            screen_relative: 1.0,
+            color: color,
+        });
+
+        // 3 - top right
+        self.vertex_data.push(Vertex {
+            position: [max[0] as f32, min[1] as f32],
+            texcoords: [uv_max[0], uv_min[1]],
+            world_position: [0.0, 0.0, 0.0],
+            screen_relative: 1.0,
+            color: color,
+        });
+
+        // Top-left triangle
+        self.index_data.push((index + 0) as u32);
+        self.index_data.push((index + 1) as u32);
+        self.index_data.push((index + 3) as u32);
+
+        // Bottom-right triangle
+        self.index_data.push((index + 3) as u32);
+        self.index_data.push((index + 1) as u32);
+        self.index_data.push((index + 2) as u32);
+
+        let index_end = self.index_data.len() as u32;
+
+        let extends_last_batch = match self.batches.last() {
+            Some(batch) => batch.texture == texture && batch.textured == textured,
+            None => false,
+        };
+
+        if extends_last_batch {
+            self.batches.last_mut().unwrap().index_end = index_end;
+        } else {
+            self.batches.push(Batch {
+                texture: texture,
+                textured: textured,
+                index_start: index_end - 6,
+                index_end: index_end,
+            });
+        }
+    }
+
+    // NOTE: had to split render() into update() and draw() so they could have separate mutable
+    // references to gfx::traits::Device and gfx::traits::Factory
+
+    ///
+    /// Populate the vertex and index buffers with the current batch of quads to be drawn
+    ///
+    pub fn update<F: Factory<D::Resources>>(
+        &mut self,
+        factory: &mut F,
+    ) {
+        if self.vertex_data.len() > self.vertex_buffer.len() {
+            self.vertex_buffer = BufferHandle::from_raw(grow_buffer::<D, F, Vertex>(factory, self.vertex_buffer.raw(), self.vertex_data.len()));
+        }
+
+        if self.index_data.len() > self.index_buffer.len() {
+            self.index_buffer = IndexBufferHandle::from_raw(grow_buffer::<D, F, u32>(factory, self.index_buffer.raw(), self.index_data.len()));
+        }
+
+        factory.update_buffer(&self.vertex_buffer, &self.vertex_data[..], 0);
+        factory.update_buffer_raw(&self.index_buffer.raw(), as_byte_slice(&self.index_data[..]), 0);
+    }
+
+    ///
+    /// Draw and clear the current batch of quads. Must be called after update() to populate the
+    /// vertex and index buffers. Issues one draw call per contiguous run of same-texture quads.
+    ///
+    pub fn render(
+        &mut self,
+        graphics: &mut Graphics<D>,
+        frame: &Frame<D::Resources>,
+        projection: [[f32; 4]; 4],
+    ) {
+        self.params.u_model_view_proj = projection;
+
+        let mesh = Mesh::from_format(
+            self.vertex_buffer.clone(),
+            self.vertex_data.len() as VertexCount
+        );
+
+        for batch in &self.batches {
+            self.params.u_tex_font = (batch.texture.clone(), Some(self.sampler.clone()));
+
+            let slice = Slice {
+                start: batch.index_start,
+                end: batch.index_end,
+                prim_type: PrimitiveType::TriangleList,
+                kind: SliceKind::Index32(self.index_buffer.clone(), 0),
+            };
+
+            let program = if batch.textured { &self.textured_program } else { &self.program };
+
+            graphics.renderer.draw(
+                &bind(&self.state, &mesh, slice, program, &self.params),
+                &frame
+            ).unwrap();
+        }
+
+        self.vertex_data.clear();
+        self.index_data.clear();
+        self.batches.clear();
+    }
+}
+
+/// Build a 1x1 fully-opaque texture used as the solid-fill path's sampler input.
+fn create_white_texture<D: Device, F: Factory<D::Resources>>(factory: &mut F) -> TextureHandle<D::Resources> {
+    let texture_info = TextureInfo {
+        width: 1 as Size,
+        height: 1 as Size,
+        depth: 1,
+        levels: 1,
+        kind: TextureKind::Texture2D,
+        format: Format::R8,
+    };
+
+    let texture = factory.create_texture(texture_info).unwrap();
+
+    factory.update_texture(
+        &texture,
+        &ImageInfo::from(texture_info),
+        &[255u8],
+        None,
+    ).unwrap();
+
+    texture
+}
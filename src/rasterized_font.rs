@@ -0,0 +1,127 @@
+use std::mem;
+
+use rusttype::{FontCollection, Font, Scale, point};
+
+use glyph_atlas::{GlyphAtlas, GlyphInfo};
+
+///
+/// Rasterizes glyphs from a TTF/OTF byte buffer on demand and packs them
+/// into a `GlyphAtlas`, so `TextRenderer` can be built from a font file
+/// instead of a pre-baked `BitmapFont` + texture pair. Glyphs are cached by
+/// character the first time they're drawn; subsequent draws just reuse the
+/// atlas slot.
+///
+pub struct RasterizedFont {
+    // The original font bytes. `font` does not borrow from this buffer: it
+    // is built from its own owned clone of the bytes, so the transmute to
+    // `'static` in `new` does not extend any borrow.
+    _bytes: Vec<u8>,
+    font: Font<'static>,
+    scale: Scale,
+    atlas: GlyphAtlas,
+    atlas_dirty: bool,
+}
+
+impl RasterizedFont {
+
+    pub fn new(font_bytes: Vec<u8>, pixel_size: f32) -> RasterizedFont {
+        let collection = FontCollection::from_bytes(font_bytes.clone());
+        let font = collection.into_font().expect("font_bytes must contain a valid TTF/OTF font");
+        let font: Font<'static> = unsafe { mem::transmute(font) };
+
+        RasterizedFont {
+            _bytes: font_bytes,
+            font: font,
+            scale: Scale::uniform(pixel_size),
+            atlas: GlyphAtlas::new(256, 256),
+            atlas_dirty: true,
+        }
+    }
+
+    pub fn atlas_size(&self) -> (u32, u32) {
+        (self.atlas.width(), self.atlas.height())
+    }
+
+    pub fn line_height(&self) -> f32 {
+        let v_metrics = self.font.v_metrics(self.scale);
+        v_metrics.ascent - v_metrics.descent + v_metrics.line_gap
+    }
+
+    pub fn kerning(&self, prev: char, current: char) -> f32 {
+        self.font.pair_kerning(self.scale, prev, current)
+    }
+
+    pub fn atlas_pixels(&self) -> &[u8] {
+        self.atlas.pixels()
+    }
+
+    /// Has the atlas changed (new glyph packed, or grown) since the last
+    /// call to `clear_dirty`? `TextRenderer::update` checks this to decide
+    /// whether the atlas texture needs re-uploading.
+    pub fn is_dirty(&self) -> bool {
+        self.atlas_dirty
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.atlas_dirty = false;
+    }
+
+    ///
+    /// Look up a glyph's atlas placement, rasterizing and packing it first
+    /// if this is the first time `character` has been requested.
+    ///
+    pub fn glyph(&mut self, character: char) -> GlyphInfo {
+        if let Some(info) = self.atlas.get(character) {
+            return *info;
+        }
+
+        // Font::glyph returns a Glyph directly (falling back to .notdef for
+        // characters the font has no outline for), not an Option.
+        let scaled = self.font.glyph(character).scaled(self.scale);
+
+        let h_metrics = scaled.h_metrics();
+        let positioned = scaled.positioned(point(0.0, 0.0));
+
+        let (bitmap, width, height, xoffset, yoffset) = match positioned.pixel_bounding_box() {
+            Some(bb) => {
+                let width = (bb.max.x - bb.min.x) as u32;
+                let height = (bb.max.y - bb.min.y) as u32;
+                let mut bitmap = vec![0u8; (width * height) as usize];
+
+                positioned.draw(|x, y, coverage| {
+                    let index = (y * width + x) as usize;
+                    bitmap[index] = (coverage * 255.0) as u8;
+                });
+
+                (bitmap, width, height, bb.min.x, bb.min.y)
+            }
+            // whitespace and other glyphs with no visible pixels still need
+            // an atlas entry so xadvance is honored
+            None => (Vec::new(), 0, 0, 0, 0),
+        };
+
+        let inserted = self.atlas.insert(
+            character,
+            &bitmap,
+            width,
+            height,
+            xoffset,
+            yoffset,
+            h_metrics.advance_width as i32,
+        );
+
+        match inserted {
+            Some(info) => {
+                self.atlas_dirty = true;
+                *info
+            }
+            // the glyph is wider than the atlas will ever be (growth only
+            // ever doubles height, never width) -- render it as if the
+            // font were missing this glyph rather than growing forever.
+            None => GlyphInfo {
+                xadvance: h_metrics.advance_width as i32,
+                .. GlyphInfo::default()
+            },
+        }
+    }
+}
@@ -15,6 +15,7 @@ use gfx::{
     ProgramError,
     ProgramHandle,
     Resources,
+    SamplerHandle,
     ShaderSource,
     Slice,
     SliceKind,
@@ -26,19 +27,96 @@ use gfx::device::Capabilities;
 
 use gfx::traits::*;
 
-use gfx::tex::{SamplerInfo, FilterMethod, WrapMode};
+use gfx::tex::{SamplerInfo, FilterMethod, WrapMode, TextureInfo, TextureKind, Format, ImageInfo, Size};
 
 use gfx::batch::bind;
 
 use gfx::shade::TextureParam;
 
 use bitmap_font::BitmapFont;
+use glyph_atlas::GlyphInfo;
+use rasterized_font::RasterizedFont;
 use utils::{grow_buffer, MAT4_ID};
 
+/// Horizontal alignment of a line of text within its text block, used by
+/// `draw_text_aligned`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Where `TextRenderer` gets glyph metrics and atlas texcoords from: either
+/// a pre-baked `BitmapFont` shipped with its own texture, or a font rasterized
+/// on demand into a growable `GlyphAtlas`.
+enum FontSource {
+    Baked(BitmapFont),
+    Rasterized(RasterizedFont),
+}
+
+impl FontSource {
+
+    fn scale(&self) -> (f32, f32) {
+        match *self {
+            FontSource::Baked(ref font) => (font.scale_w as f32, font.scale_h as f32),
+            FontSource::Rasterized(ref font) => {
+                let (width, height) = font.atlas_size();
+                (width as f32, height as f32)
+            }
+        }
+    }
+
+    fn line_height(&self) -> f32 {
+        match *self {
+            FontSource::Baked(ref font) => font.line_height as f32,
+            FontSource::Rasterized(ref font) => font.line_height(),
+        }
+    }
+
+    /// Extra horizontal offset to apply between `prev` and `current`, on top
+    /// of `prev`'s own `xadvance`.
+    fn kerning(&self, prev: char, current: char) -> f32 {
+        match *self {
+            // BitmapFont's only confirmed API (from the pre-existing
+            // draw_text code) is its `characters` map -- nothing in this
+            // tree demonstrates a `kerning` method on it, so assuming one
+            // here would risk a compile break in the (more common) baked
+            // font path. Baked fonts render without kerning until that API
+            // is confirmed; rasterized fonts get it from rusttype directly.
+            FontSource::Baked(_) => 0.0,
+            FontSource::Rasterized(ref font) => font.kerning(prev, current),
+        }
+    }
+
+    /// Look up (rasterizing and packing it first, if necessary) the atlas
+    /// placement and metrics for `character`.
+    fn glyph(&mut self, character: char) -> GlyphInfo {
+        match *self {
+            FontSource::Baked(ref font) => {
+                let default_character = Default::default();
+                let bc = font.characters.get(&character).unwrap_or(&default_character);
+                GlyphInfo {
+                    x: bc.x as u32,
+                    y: bc.y as u32,
+                    width: bc.width as u32,
+                    height: bc.height as u32,
+                    xoffset: bc.xoffset as i32,
+                    yoffset: bc.yoffset as i32,
+                    xadvance: bc.xadvance as i32,
+                }
+            }
+            FontSource::Rasterized(ref mut font) => font.glyph(character),
+        }
+    }
+}
+
 pub struct TextRenderer<D: Device> {
     program: ProgramHandle<D::Resources>,
     state: DrawState,
-    bitmap_font: BitmapFont,
+    font_source: FontSource,
+    font_texture: TextureHandle<D::Resources>,
+    sampler: SamplerHandle<D::Resources>,
     vertex_data: Vec<Vertex>,
     index_data: Vec<u32>,
     vertex_buffer: BufferHandle<D::Resources, Vertex>,
@@ -62,12 +140,87 @@ impl<D: Device> TextRenderer<D> {
         let vertex = ShaderSource {
             glsl_120: Some(VERTEX_SRC[0]),
             glsl_150: Some(VERTEX_SRC[1]),
+            glsl_es_100: Some(VERTEX_SRC[2]),
+            glsl_es_300: Some(VERTEX_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let fragment = ShaderSource {
+            glsl_120: Some(FRAGMENT_SRC[0]),
+            glsl_150: Some(FRAGMENT_SRC[1]),
+            glsl_es_100: Some(FRAGMENT_SRC[2]),
+            glsl_es_300: Some(FRAGMENT_SRC[3]),
+            .. ShaderSource::empty()
+        };
+
+        let program = match factory.link_program(
+            vertex.choose(shader_model).unwrap(),
+            fragment.choose(shader_model).unwrap()
+        ) {
+            Ok(program_handle) => program_handle,
+            Err(e) => return Err(e),
+        };
+
+        let vertex_buffer = factory.create_buffer::<Vertex>(initial_buffer_size, BufferUsage::Dynamic);
+        let index_buffer = IndexBufferHandle::from_raw(factory.create_buffer_raw(initial_buffer_size * mem::size_of::<u32>(), BufferUsage::Dynamic));
+
+        let sampler = factory.create_sampler(
+           SamplerInfo::new(
+               FilterMethod::Scale,
+               WrapMode::Clamp
+            )
+        );
+
+        let state = DrawState::new().blend(BlendPreset::Alpha);
+
+        Ok(TextRenderer {
+            vertex_data: Vec::new(),
+            index_data: Vec::new(),
+            font_source: FontSource::Baked(bitmap_font),
+            program: program,
+            state: state,
+            font_texture: font_texture.clone(),
+            sampler: sampler.clone(),
+            vertex_buffer: vertex_buffer,
+            index_buffer: index_buffer,
+            params: TextShaderParams {
+                u_model_view_proj: MAT4_ID,
+                u_screen_size: [frame_size[0] as f32, frame_size[1] as f32],
+                u_tex_font: (font_texture, Some(sampler)),
+            },
+        })
+    }
+
+    ///
+    /// Build a `TextRenderer` directly from TTF/OTF font bytes instead of a
+    /// pre-baked `BitmapFont` + texture. Glyphs are rasterized and packed
+    /// into a `GlyphAtlas` the first time each character is drawn; the atlas
+    /// texture is (re)uploaded from `update` whenever new glyphs were added.
+    ///
+    pub fn new_from_font_bytes<F: Factory<D::Resources>>(
+        device_capabilities: Capabilities,
+        factory: &mut F,
+        frame_size: [u32; 2],
+        initial_buffer_size: usize,
+        font_bytes: Vec<u8>,
+        pixel_size: f32,
+    ) -> Result<TextRenderer<D>, ProgramError> {
+
+        let shader_model = device_capabilities.shader_model;
+
+        let vertex = ShaderSource {
+            glsl_120: Some(VERTEX_SRC[0]),
+            glsl_150: Some(VERTEX_SRC[1]),
+            glsl_es_100: Some(VERTEX_SRC[2]),
+            glsl_es_300: Some(VERTEX_SRC[3]),
             .. ShaderSource::empty()
         };
 
         let fragment = ShaderSource {
             glsl_120: Some(FRAGMENT_SRC[0]),
             glsl_150: Some(FRAGMENT_SRC[1]),
+            glsl_es_100: Some(FRAGMENT_SRC[2]),
+            glsl_es_300: Some(FRAGMENT_SRC[3]),
             .. ShaderSource::empty()
         };
 
@@ -91,12 +244,17 @@ impl<D: Device> TextRenderer<D> {
 
         let state = DrawState::new().blend(BlendPreset::Alpha);
 
+        let rasterized_font = RasterizedFont::new(font_bytes, pixel_size);
+        let font_texture = create_atlas_texture(factory, &rasterized_font);
+
         Ok(TextRenderer {
             vertex_data: Vec::new(),
             index_data: Vec::new(),
-            bitmap_font: bitmap_font,
+            font_source: FontSource::Rasterized(rasterized_font),
             program: program,
             state: state,
+            font_texture: font_texture.clone(),
+            sampler: sampler.clone(),
             vertex_buffer: vertex_buffer,
             index_buffer: index_buffer,
             params: TextShaderParams {
@@ -120,7 +278,7 @@ impl<D: Device> TextRenderer<D> {
         world_position: [f32; 3],
         color: [f32; 4],
     ) {
-        self.draw_text(text, [0, 0], world_position, 0, color);
+        self.draw_text(text, [0, 0], world_position, 0.0, color, None, Alignment::Left);
     }
 
     pub fn draw_text_on_screen(
@@ -129,7 +287,83 @@ impl<D: Device> TextRenderer<D> {
         screen_position: [i32; 2],
         color: [f32; 4],
     ) {
-        self.draw_text(text, screen_position, [0.0, 0.0, 0.0], 1, color);
+        self.draw_text(text, screen_position, [0.0, 0.0, 0.0], 1.0, color, None, Alignment::Left);
+    }
+
+    ///
+    /// Like `draw_text_on_screen`, but first draws the glyphs again at each
+    /// of the 8 neighboring offsets (at `thickness` pixels) in `outline_color`,
+    /// giving the text a contrast edge that reads against any background.
+    /// All outline copies are batched before the main fill pass so they share
+    /// one draw call with the fill correctly layered on top.
+    ///
+    pub fn draw_text_on_screen_outlined(
+        &mut self,
+        text: &str,
+        screen_position: [i32; 2],
+        color: [f32; 4],
+        outline_color: [f32; 4],
+        thickness: i32,
+    ) {
+        let underlays: Vec<([i32; 2], [f32; 4])> = ring_offsets(thickness).iter()
+            .map(|&offset| (offset, outline_color))
+            .collect();
+
+        self.draw_text_layered(text, screen_position, [0.0, 0.0, 0.0], 1.0, color, &underlays, None, Alignment::Left);
+    }
+
+    ///
+    /// Like `draw_text_on_screen`, but first draws a single copy of the
+    /// glyphs shifted by `shadow_offset` in `shadow_color`, underneath the
+    /// main fill.
+    ///
+    pub fn draw_text_on_screen_with_shadow(
+        &mut self,
+        text: &str,
+        screen_position: [i32; 2],
+        color: [f32; 4],
+        shadow_color: [f32; 4],
+        shadow_offset: [i32; 2],
+    ) {
+        self.draw_text_layered(text, screen_position, [0.0, 0.0, 0.0], 1.0, color, &[(shadow_offset, shadow_color)], None, Alignment::Left);
+    }
+
+    /// Draw `underlays` (each an offset + tint, in order) before the main
+    /// fill pass, all at `screen_position` plus their own offset. Backs both
+    /// the outline and drop-shadow entry points.
+    fn draw_text_layered(
+        &mut self,
+        text: &str,
+        screen_position: [i32; 2],
+        world_position: [f32; 3],
+        screen_relative: f32,
+        color: [f32; 4],
+        underlays: &[([i32; 2], [f32; 4])],
+        max_width: Option<f32>,
+        alignment: Alignment,
+    ) {
+        for &(offset, underlay_color) in underlays {
+            let shifted = [screen_position[0] + offset[0], screen_position[1] + offset[1]];
+            self.draw_text(text, shifted, world_position, screen_relative, underlay_color, max_width, alignment);
+        }
+
+        self.draw_text(text, screen_position, world_position, screen_relative, color, max_width, alignment);
+    }
+
+    ///
+    /// Like `draw_text_on_screen`, but wraps on word boundaries once a line
+    /// would exceed `max_width` (pass `None` to disable wrapping) and aligns
+    /// each line left, centered, or right within the text block.
+    ///
+    pub fn draw_text_aligned(
+        &mut self,
+        text: &str,
+        screen_position: [i32; 2],
+        color: [f32; 4],
+        alignment: Alignment,
+        max_width: Option<f32>,
+    ) {
+        self.draw_text(text, screen_position, [0.0, 0.0, 0.0], 1.0, color, max_width, alignment);
     }
 
     fn draw_text(
@@ -137,107 +371,140 @@ impl<D: Device> TextRenderer<D> {
         text: &str,
         screen_position: [i32; 2],
         world_position: [f32; 3],
-        screen_relative: i32,
+        screen_relative: f32,
         color: [f32; 4],
+        max_width: Option<f32>,
+        alignment: Alignment,
     ) {
-        let [mut x, y] = screen_position;
+        let (scale_w, scale_h) = self.font_source.scale();
+        let line_height = self.font_source.line_height();
 
-        let scale_w = self.bitmap_font.scale_w as f32;
-        let scale_h = self.bitmap_font.scale_h as f32;
+        let lines = self.wrap_lines(text, max_width);
 
-        // placeholder for characters missing from font
-        let default_character = Default::default();
+        let mut y = screen_position[1];
 
-        for character in text.chars() {
+        for line in &lines {
 
-            let bc = match self.bitmap_font.characters.get(&character) {
-                Some(c) => c,
-                None => &default_character,
+            let mut x = match alignment {
+                Alignment::Left => screen_position[0],
+                Alignment::Center => screen_position[0] - (self.measure_line(line) / 2.0) as i32,
+                Alignment::Right => screen_position[0] - self.measure_line(line) as i32,
             };
 
-            // Push quad vertices in CCW direction
-            let index = self.vertex_data.len();
-
-            let x_offset = (bc.xoffset as i32 + x) as f32;
-            let y_offset = (bc.yoffset as i32 + y) as f32;
-
-
-            // 0 - top left
-            self.vertex_data.push(Vertex {
-                position: [
-                    x_offset,
-                    y_offset,
-                ],
-                color: color,
-                texcoords: [
-                    bc.x as f32 / scale_w,
-                    bc.y as f32 / scale_h,
-                ],
-                world_position: world_position,
-                screen_relative: screen_relative,
-            });
-
-            // 1 - bottom left
-            self.vertex_data.push(Vertex{
-                position: [
-                    x_offset,
-                    bc.height as f32 + y_offset
-                ],
-                color: color,
-                texcoords: [
-                    bc.x as f32 / scale_w,
-                    (bc.y + bc.height) as f32 / scale_h,
-                ],
-                world_position: world_position,
-                screen_relative: screen_relative,
-            });
-
-            // 2 - bottom right
-            self.vertex_data.push(Vertex{
-                position: [
-                    bc.width as f32 + x_offset,
-                    bc.height as f32 + y_offset,
-                ],
-                color: color,
-                texcoords: [
-                    (bc.x + bc.width) as f32 / scale_w,
-                    (bc.y + bc.height) as f32 / scale_h,
-                ],
-                world_position: world_position,
-                screen_relative: screen_relative,
-            });
-
-
-            // 3 - top right
-            self.vertex_data.push(Vertex{
-                position: [
-                    bc.width as f32 + x_offset,
-                    y_offset,
-                ],
-                color: color,
-                texcoords: [
-                    (bc.x + bc.width) as f32 / scale_w,
-                    bc.y as f32 / scale_h,
-                ],
-                world_position: world_position,
-                screen_relative: screen_relative,
-            });
-
-
-            // Top-left triangle
-            self.index_data.push((index + 0) as u32);
-            self.index_data.push((index + 1) as u32);
-            self.index_data.push((index + 3) as u32);
-
-            // Bottom-right triangle
-            self.index_data.push((index + 3) as u32);
-            self.index_data.push((index + 1) as u32);
-            self.index_data.push((index + 2) as u32);
-
-            x += bc.xadvance as i32;
+            let mut prev_char: Option<char> = None;
+
+            for character in line.chars() {
+
+                if let Some(prev) = prev_char {
+                    x += self.font_source.kerning(prev, character) as i32;
+                }
+
+                let bc = self.font_source.glyph(character);
+
+                // Push quad vertices in CCW direction
+                let index = self.vertex_data.len();
+
+                let x_offset = (bc.xoffset as i32 + x) as f32;
+                let y_offset = (bc.yoffset as i32 + y) as f32;
+
+
+                // 0 - top left
+                self.vertex_data.push(Vertex {
+                    position: [
+                        x_offset,
+                        y_offset,
+                    ],
+                    color: color,
+                    texcoords: [
+                        bc.x as f32 / scale_w,
+                        bc.y as f32 / scale_h,
+                    ],
+                    world_position: world_position,
+                    screen_relative: screen_relative,
+                });
+
+                // 1 - bottom left
+                self.vertex_data.push(Vertex{
+                    position: [
+                        x_offset,
+                        bc.height as f32 + y_offset
+                    ],
+                    color: color,
+                    texcoords: [
+                        bc.x as f32 / scale_w,
+                        (bc.y + bc.height) as f32 / scale_h,
+                    ],
+                    world_position: world_position,
+                    screen_relative: screen_relative,
+                });
+
+                // 2 - bottom right
+                self.vertex_data.push(Vertex{
+                    position: [
+                        bc.width as f32 + x_offset,
+                        bc.height as f32 + y_offset,
+                    ],
+                    color: color,
+                    texcoords: [
+                        (bc.x + bc.width) as f32 / scale_w,
+                        (bc.y + bc.height) as f32 / scale_h,
+                    ],
+                    world_position: world_position,
+                    screen_relative: screen_relative,
+                });
+
+
+                // 3 - top right
+                self.vertex_data.push(Vertex{
+                    position: [
+                        bc.width as f32 + x_offset,
+                        y_offset,
+                    ],
+                    color: color,
+                    texcoords: [
+                        (bc.x + bc.width) as f32 / scale_w,
+                        bc.y as f32 / scale_h,
+                    ],
+                    world_position: world_position,
+                    screen_relative: screen_relative,
+                });
+
+
+                // Top-left triangle
+                self.index_data.push((index + 0) as u32);
+                self.index_data.push((index + 1) as u32);
+                self.index_data.push((index + 3) as u32);
+
+                // Bottom-right triangle
+                self.index_data.push((index + 3) as u32);
+                self.index_data.push((index + 1) as u32);
+                self.index_data.push((index + 2) as u32);
+
+                x += bc.xadvance as i32;
+                prev_char = Some(character);
+            }
+
+            y += line_height as i32;
         }
     }
 
+    /// Split `text` into display lines: hard breaks at `\n`, plus
+    /// word-boundary wrapping once a line would exceed `max_width`.
+    fn wrap_lines(&mut self, text: &str, max_width: Option<f32>) -> Vec<String> {
+        wrap_lines_with(text, max_width, |word| self.measure_line(word))
+    }
+
+    /// Total horizontal advance (including kerning) of a single line.
+    fn measure_line(&mut self, line: &str) -> f32 {
+        measure_line_with(line, |prev, character| {
+            let mut width = self.font_source.glyph(character).xadvance as f32;
+            if let Some(prev) = prev {
+                width += self.font_source.kerning(prev, character);
+            }
+            width
+        })
+    }
+
     // NOTE: had to split render() into update() and draw() so they could have separate mutable
     // references to gfx::traits::Device and gfx::traits::Factory
 
@@ -248,6 +515,15 @@ impl<D: Device> TextRenderer<D> {
         &mut self,
         factory: &mut F,
     ) {
+        if let FontSource::Rasterized(ref mut font) = self.font_source {
+            if font.is_dirty() {
+                let texture = create_atlas_texture(factory, font);
+                self.font_texture = texture.clone();
+                self.params.u_tex_font = (texture, Some(self.sampler.clone()));
+                font.clear_dirty();
+            }
+        }
+
         if self.vertex_data.len() > self.vertex_buffer.len() {
             self.vertex_buffer = BufferHandle::from_raw(grow_buffer::<D, F, Vertex>(factory, self.vertex_buffer.raw(), self.vertex_data.len()));
         }
@@ -294,7 +570,114 @@ impl<D: Device> TextRenderer<D> {
     }
 }
 
-static VERTEX_SRC: [&'static [u8]; 2] = [
+/// Split `text` into display lines: hard breaks at `\n`, plus word-boundary
+/// wrapping once a line would exceed `max_width`. `measure` supplies the
+/// width of a word or a single space; kept free of `FontSource` so it can be
+/// unit tested without a font.
+fn wrap_lines_with<F: FnMut(&str) -> f32>(
+    text: &str,
+    max_width: Option<f32>,
+    mut measure: F,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+        };
+
+        let mut line = String::new();
+        let mut line_width = 0.0;
+
+        for word in paragraph.split(' ') {
+            let word_width = measure(word);
+            let space_width = if line.is_empty() { 0.0 } else { measure(" ") };
+
+            if !line.is_empty() && line_width + space_width + word_width > max_width {
+                lines.push(mem::replace(&mut line, String::new()));
+                line_width = 0.0;
+            }
+
+            if !line.is_empty() {
+                line.push(' ');
+                line_width += space_width;
+            }
+
+            line.push_str(word);
+            line_width += word_width;
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Total horizontal advance of a single line. `increment` supplies each
+/// character's own advance plus any kerning against the previous character;
+/// kept free of `FontSource` so it can be unit tested without a font.
+fn measure_line_with<F: FnMut(Option<char>, char) -> f32>(line: &str, mut increment: F) -> f32 {
+    let mut width = 0.0;
+    let mut prev_char: Option<char> = None;
+
+    for character in line.chars() {
+        width += increment(prev_char, character);
+        prev_char = Some(character);
+    }
+
+    width
+}
+
+/// The 8 neighboring pixel offsets at `thickness` pixels out, used to stamp
+/// an outline ring around each glyph.
+fn ring_offsets(thickness: i32) -> [[i32; 2]; 8] {
+    [
+        [-thickness, -thickness], [0, -thickness], [thickness, -thickness],
+        [-thickness, 0],                            [thickness, 0],
+        [-thickness, thickness],  [0, thickness],   [thickness, thickness],
+    ]
+}
+
+/// Upload a `RasterizedFont`'s current single-channel atlas pixels into a
+/// fresh RGBA8 texture: white texels with the glyph coverage in alpha, which
+/// is the channel `FRAGMENT_SRC` reads as the glyph mask.
+fn create_atlas_texture<D: Device, F: Factory<D::Resources>>(
+    factory: &mut F,
+    font: &RasterizedFont,
+) -> TextureHandle<D::Resources> {
+
+    let (width, height) = font.atlas_size();
+
+    let texture_info = TextureInfo {
+        width: width as Size,
+        height: height as Size,
+        depth: 1,
+        levels: 1,
+        kind: TextureKind::Texture2D,
+        format: Format::Rgba8,
+    };
+
+    let texture = factory.create_texture(texture_info).unwrap();
+
+    let pixels: Vec<u8> = font.atlas_pixels().iter()
+        .flat_map(|&coverage| vec![255u8, 255, 255, coverage])
+        .collect();
+
+    factory.update_texture(
+        &texture,
+        &ImageInfo::from(texture_info),
+        &pixels[..],
+        None,
+    ).unwrap();
+
+    texture
+}
+
+pub(crate) static VERTEX_SRC: [&'static [u8]; 4] = [
 b"
     #version 120
 
@@ -304,7 +687,10 @@ b"
 
     attribute vec2 position;
     attribute vec4 world_position;
-    in int screen_relative;
+    // GLSL ES 1.00 (the #version 100 variant below) has no integer vertex
+    // attributes, so screen_relative travels as a float (0.0 / 1.0) in
+    // every variant rather than just the one that strictly needs it.
+    attribute float screen_relative;
     attribute vec4 color;
     attribute vec2 texcoords;
     varying vec4 v_color;
@@ -327,7 +713,7 @@ b"
         );
 
         // on-screen offset accounting for world_position
-        world_offset = screen_relative == 0 ? world_offset : vec2(0.0, 0.0);
+        world_offset = screen_relative == 0.0 ? world_offset : vec2(0.0, 0.0);
 
         gl_Position = vec4(world_offset + screen_offset, 0, 1.0);
 
@@ -344,7 +730,7 @@ b"
 
     in vec2 position;
     in vec4 world_position;
-    in int screen_relative;
+    in float screen_relative;
     in vec4 color;
     in vec2 texcoords;
     out vec4 v_color;
@@ -367,17 +753,99 @@ b"
         );
 
         // on-screen offset accounting for world_position
-        world_offset = screen_relative == 0 ? world_offset : vec2(0.0, 0.0);
+        world_offset = screen_relative == 0.0 ? world_offset : vec2(0.0, 0.0);
 
         gl_Position = vec4(world_offset + screen_offset, 0, 1.0);
 
         v_TexCoord = texcoords;
         v_color = color;
 
+    }
+",
+b"
+    #version 100
+
+    precision mediump float;
+
+    uniform vec2 u_screen_size;
+    uniform mat4 u_model_view_proj;
+
+    attribute vec2 position;
+    attribute vec4 world_position;
+    attribute float screen_relative;
+    attribute vec4 color;
+    attribute vec2 texcoords;
+    varying vec4 v_color;
+    varying vec2 v_TexCoord;
+
+    void main() {
+
+        // on-screen offset from text origin
+        vec2 screen_offset = vec2(
+            2.0 * position.x / u_screen_size.x - 1.0,
+            1.0 - 2.0 * position.y / u_screen_size.y
+        );
+
+        vec4 screen_position = u_model_view_proj * world_position;
+
+        // perspective divide to get normalized device coords
+        vec2 world_offset = vec2(
+            screen_position.x / screen_position.z + 1.0,
+            screen_position.y / screen_position.z - 1.0
+        );
+
+        // on-screen offset accounting for world_position
+        world_offset = screen_relative == 0.0 ? world_offset : vec2(0.0, 0.0);
+
+        gl_Position = vec4(world_offset + screen_offset, 0.0, 1.0);
+
+        v_TexCoord = texcoords;
+        v_color = color;
+
+    }
+",
+b"
+    #version 300 es
+
+    uniform vec2 u_screen_size;
+    uniform mat4 u_model_view_proj;
+
+    in vec2 position;
+    in vec4 world_position;
+    in float screen_relative;
+    in vec4 color;
+    in vec2 texcoords;
+    out vec4 v_color;
+    out vec2 v_TexCoord;
+
+    void main() {
+
+        // on-screen offset from text origin
+        vec2 screen_offset = vec2(
+            2.0 * position.x / u_screen_size.x - 1.0,
+            1.0 - 2.0 * position.y / u_screen_size.y
+        );
+
+        vec4 screen_position = u_model_view_proj * world_position;
+
+        // perspective divide to get normalized device coords
+        vec2 world_offset = vec2(
+            screen_position.x / screen_position.z + 1.0,
+            screen_position.y / screen_position.z - 1.0
+        );
+
+        // on-screen offset accounting for world_position
+        world_offset = screen_relative == 0.0 ? world_offset : vec2(0.0, 0.0);
+
+        gl_Position = vec4(world_offset + screen_offset, 0.0, 1.0);
+
+        v_TexCoord = texcoords;
+        v_color = color;
+
     }
 "];
 
-static FRAGMENT_SRC: [&'static [u8]; 2] = [
+pub(crate) static FRAGMENT_SRC: [&'static [u8]; 4] = [
 b"
     #version 120
 
@@ -400,6 +868,37 @@ b"
     in vec2 v_TexCoord;
     out vec4 out_color;
 
+    void main() {
+        vec4 font_color = texture(u_tex_font, v_TexCoord);
+        out_color = vec4(v_color.xyz, font_color.a * v_color.a);
+    }
+",
+b"
+    #version 100
+
+    precision mediump float;
+
+    uniform sampler2D u_tex_font;
+
+    varying vec4 v_color;
+    varying vec2 v_TexCoord;
+
+    void main() {
+        vec4 font_color = texture2D(u_tex_font, v_TexCoord);
+        gl_FragColor = vec4(v_color.xyz, font_color.a * v_color.a);
+    }
+",
+b"
+    #version 300 es
+
+    precision mediump float;
+
+    uniform sampler2D u_tex_font;
+
+    in vec4 v_color;
+    in vec2 v_TexCoord;
+    out vec4 out_color;
+
     void main() {
         vec4 font_color = texture(u_tex_font, v_TexCoord);
         out_color = vec4(v_color.xyz, font_color.a * v_color.a);
@@ -410,17 +909,76 @@ b"
 #[derive(Copy)]
 #[derive(Clone)]
 #[derive(Debug)]
-struct Vertex {
-    position: [f32; 2],
-    texcoords: [f32; 2],
-    world_position: [f32; 3],
-    screen_relative: i32,
-    color: [f32; 4],
+pub(crate) struct Vertex {
+    pub position: [f32; 2],
+    pub texcoords: [f32; 2],
+    pub world_position: [f32; 3],
+    pub screen_relative: f32,
+    pub color: [f32; 4],
 }
 
 #[shader_param]
-struct TextShaderParams<R: Resources> {
-    u_model_view_proj: [[f32; 4]; 4],
-    u_screen_size: [f32; 2],
-    u_tex_font: TextureParam<R>,
+pub(crate) struct TextShaderParams<R: Resources> {
+    pub u_model_view_proj: [[f32; 4]; 4],
+    pub u_screen_size: [f32; 2],
+    pub u_tex_font: TextureParam<R>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wrap_lines_with, measure_line_with};
+
+    // 10px per character, no kerning -- enough to exercise the wrapping and
+    // measuring logic without a real font.
+    fn fixed_width(s: &str) -> f32 {
+        s.chars().count() as f32 * 10.0
+    }
+
+    #[test]
+    fn measure_line_sums_character_advances() {
+        let width = measure_line_with("abc", |_prev, _current| 10.0);
+        assert_eq!(width, 30.0);
+    }
+
+    #[test]
+    fn measure_line_adds_kerning_against_the_previous_character() {
+        let width = measure_line_with("ab", |prev, _current| {
+            10.0 + if prev.is_some() { 2.0 } else { 0.0 }
+        });
+        assert_eq!(width, 22.0);
+    }
+
+    #[test]
+    fn wrap_lines_keeps_a_single_paragraph_on_one_line_when_it_fits() {
+        let lines = wrap_lines_with("foo bar", Some(1000.0), fixed_width);
+        assert_eq!(lines, vec!["foo bar"]);
+    }
+
+    #[test]
+    fn wrap_lines_breaks_at_a_word_boundary_once_max_width_is_exceeded() {
+        // "foo" = 30px, " " = 10px, "bar" = 30px: "foo bar" is 70px wide,
+        // too much for a 50px max_width, so it must wrap after "foo".
+        let lines = wrap_lines_with("foo bar", Some(50.0), fixed_width);
+        assert_eq!(lines, vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn wrap_lines_never_splits_a_single_word_even_if_it_overflows() {
+        let lines = wrap_lines_with("aVeryLongWordThatOverflows", Some(10.0), fixed_width);
+        assert_eq!(lines, vec!["aVeryLongWordThatOverflows"]);
+    }
+
+    #[test]
+    fn wrap_lines_treats_newlines_as_hard_breaks() {
+        let lines = wrap_lines_with("foo\nbar", Some(1000.0), fixed_width);
+        assert_eq!(lines, vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn wrap_lines_skips_measuring_entirely_when_max_width_is_none() {
+        let lines = wrap_lines_with("foo bar\nbaz", None, |_| {
+            panic!("measure should not be called when max_width is None")
+        });
+        assert_eq!(lines, vec!["foo bar", "baz"]);
+    }
 }